@@ -0,0 +1,114 @@
+use syn::punctuated::Punctuated;
+use syn::{Attribute, Expr, LitStr, Token, Type, Variant, WherePredicate};
+
+use crate::ctxt::Ctxt;
+use crate::discriminant::is_valid_discriminant_type;
+
+/// Container-level `#[xdr(...)]` configuration.
+#[derive(Default)]
+pub(crate) struct ContainerAttrs {
+    /// `#[xdr(bound = "T: Trait, ...")]`: replaces the where-clause predicates
+    /// inferred by [`crate::bound`] when the heuristic picks the wrong ones.
+    pub(crate) bound: Option<Vec<WherePredicate>>,
+    /// `#[xdr(discriminant_type = "i32" | "u32" | "bool" | <path>)]`: the
+    /// wire type of the union discriminant, per RFC 4506. Defaults to `u32`.
+    pub(crate) discriminant_type: Option<Type>,
+}
+
+/// Parses every `#[xdr(...)]` attribute on a container (the `enum` itself).
+pub(crate) fn parse_container_attrs(cx: &Ctxt, attrs: &[Attribute]) -> ContainerAttrs {
+    let mut container = ContainerAttrs::default();
+
+    for attr in attrs {
+        if !attr.path().is_ident("xdr") {
+            continue;
+        }
+
+        let result = attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("bound") {
+                let lit: LitStr = meta.value()?.parse()?;
+                let predicates = lit
+                    .parse_with(Punctuated::<WherePredicate, Token![,]>::parse_terminated)?
+                    .into_iter()
+                    .collect();
+                container.bound = Some(predicates);
+                Ok(())
+            } else if meta.path.is_ident("discriminant_type") {
+                let lit: LitStr = meta.value()?.parse()?;
+                let ty: Type = lit.parse()?;
+                if !is_valid_discriminant_type(&ty) {
+                    return Err(syn::Error::new_spanned(
+                        &lit,
+                        "discriminant_type must be `bool`, a built-in integer type, or a path \
+                         naming an enum type, per RFC 4506's int/unsigned int/bool/enum union \
+                         discriminants",
+                    ));
+                }
+                container.discriminant_type = Some(ty);
+                Ok(())
+            } else {
+                Err(meta.error(
+                    "unknown `xdr` container attribute, expected `bound` or `discriminant_type`",
+                ))
+            }
+        });
+
+        if let Err(e) = result {
+            cx.syn_error(e);
+        }
+    }
+
+    container
+}
+
+/// Per-variant `#[xdr(...)]` configuration.
+///
+/// Parsed with [`syn::Attribute::parse_nested_meta`] in the style of
+/// serde_derive's `internals::attr`, so the same `#[xdr(...)]` namespace
+/// serves both these keys and the container-level ones above.
+#[derive(Default)]
+pub(crate) struct VariantAttrs {
+    /// `#[xdr(default)]`: this variant is the catch-all default arm.
+    pub(crate) default: bool,
+    /// `#[xdr(discriminant = <expr>)]`: explicit discriminant, overriding
+    /// both the implicit counter and any Rust `= <expr>` on the variant.
+    pub(crate) discriminant: Option<Expr>,
+    /// `#[xdr(skip)]`: give this variant no wire form. It gets no deserialize
+    /// branch, and its serialize match arm only returns an error.
+    pub(crate) skip: bool,
+}
+
+/// Parses every `#[xdr(...)]` attribute on `variant`, reporting unknown keys
+/// as spanned errors on `cx` rather than silently ignoring them.
+pub(crate) fn parse_variant_attrs(cx: &Ctxt, variant: &Variant) -> VariantAttrs {
+    let mut attrs = VariantAttrs::default();
+
+    for attr in &variant.attrs {
+        if !attr.path().is_ident("xdr") {
+            continue;
+        }
+
+        let result = attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("default") {
+                attrs.default = true;
+                Ok(())
+            } else if meta.path.is_ident("discriminant") {
+                attrs.discriminant = Some(meta.value()?.parse()?);
+                Ok(())
+            } else if meta.path.is_ident("skip") {
+                attrs.skip = true;
+                Ok(())
+            } else {
+                Err(meta.error(
+                    "unknown `xdr` variant attribute, expected `default`, `discriminant`, or `skip`",
+                ))
+            }
+        });
+
+        if let Err(e) = result {
+            cx.syn_error(e);
+        }
+    }
+
+    attrs
+}
@@ -0,0 +1,139 @@
+use std::collections::HashSet;
+
+use syn::{
+    GenericArgument, GenericParam, Generics, Ident, Lifetime, LifetimeParam, PathArguments, Type,
+    Variant, WherePredicate, parse_quote,
+};
+
+/// Type parameters declared on `generics` that actually appear somewhere in
+/// the field types of `variants`. Mirrors serde_derive's `bound::with_bound`
+/// heuristic: only type parameters that are actually used need a trait bound.
+fn used_type_params(generics: &Generics, variants: &[&Variant]) -> HashSet<Ident> {
+    let declared: HashSet<Ident> = generics.type_params().map(|tp| tp.ident.clone()).collect();
+    let mut used = HashSet::new();
+    for variant in variants {
+        for field in variant.fields.iter() {
+            collect_idents_in_type(&field.ty, &declared, &mut used);
+        }
+    }
+    used
+}
+
+fn collect_idents_in_type(ty: &Type, declared: &HashSet<Ident>, used: &mut HashSet<Ident>) {
+    match ty {
+        Type::Path(type_path) => {
+            if let Some(qself) = &type_path.qself {
+                collect_idents_in_type(&qself.ty, declared, used);
+            }
+            if type_path.qself.is_none() && type_path.path.segments.len() == 1 {
+                let ident = &type_path.path.segments[0].ident;
+                if declared.contains(ident) {
+                    used.insert(ident.clone());
+                }
+            }
+            for segment in &type_path.path.segments {
+                if let PathArguments::AngleBracketed(args) = &segment.arguments {
+                    for arg in &args.args {
+                        if let GenericArgument::Type(inner) = arg {
+                            collect_idents_in_type(inner, declared, used);
+                        }
+                    }
+                }
+            }
+        }
+        Type::Reference(r) => collect_idents_in_type(&r.elem, declared, used),
+        Type::Tuple(t) => {
+            for elem in &t.elems {
+                collect_idents_in_type(elem, declared, used);
+            }
+        }
+        Type::Array(a) => collect_idents_in_type(&a.elem, declared, used),
+        Type::Slice(s) => collect_idents_in_type(&s.elem, declared, used),
+        Type::Paren(p) => collect_idents_in_type(&p.elem, declared, used),
+        Type::Group(g) => collect_idents_in_type(&g.elem, declared, used),
+        Type::Ptr(p) => collect_idents_in_type(&p.elem, declared, used),
+        _ => {}
+    }
+}
+
+/// Generics for the `Serialize` impl: a clone of the enum's own generics with
+/// either the caller-supplied `#[xdr(bound = "...")]` predicates, or (absent
+/// that) an inferred `T: ::serde::Serialize` predicate for every type
+/// parameter that appears in a variant field.
+pub(crate) fn with_serialize_bound(
+    generics: &Generics,
+    variants: &[&Variant],
+    explicit_bound: Option<&[WherePredicate]>,
+) -> Generics {
+    let mut generics = generics.clone();
+
+    if let Some(predicates) = explicit_bound {
+        generics
+            .make_where_clause()
+            .predicates
+            .extend(predicates.iter().cloned());
+        return generics;
+    }
+
+    let used = used_type_params(&generics, variants);
+    let bounded_params: Vec<Ident> = generics
+        .type_params()
+        .map(|tp| tp.ident.clone())
+        .filter(|ident| used.contains(ident))
+        .collect();
+    for ident in bounded_params {
+        generics
+            .make_where_clause()
+            .predicates
+            .push(parse_quote! { #ident: ::serde::Serialize });
+    }
+    generics
+}
+
+/// Generics for the `Deserialize<'de>` impl. Returns the impl-side generics
+/// (the enum's own generics plus a fresh `'de` that outlives every lifetime
+/// already declared on the enum) together with the enum's own generics
+/// unchanged, so callers can `split_for_impl()` each independently: the impl
+/// generics carry `'de` and the where-clause, while the type generics must
+/// not mention `'de` since the enum itself has no such lifetime.
+pub(crate) fn deserialize_generics(
+    generics: &Generics,
+    variants: &[&Variant],
+    explicit_bound: Option<&[WherePredicate]>,
+) -> (Generics, Generics) {
+    let type_generics = generics.clone();
+    let mut impl_generics = generics.clone();
+
+    let outlived: Vec<Lifetime> = impl_generics
+        .lifetimes()
+        .map(|lp| lp.lifetime.clone())
+        .collect();
+    let mut de_lifetime: LifetimeParam = parse_quote! { 'de };
+    de_lifetime.bounds = outlived.into_iter().collect();
+    impl_generics
+        .params
+        .insert(0, GenericParam::Lifetime(de_lifetime));
+
+    if let Some(predicates) = explicit_bound {
+        impl_generics
+            .make_where_clause()
+            .predicates
+            .extend(predicates.iter().cloned());
+        return (impl_generics, type_generics);
+    }
+
+    let used = used_type_params(&impl_generics, variants);
+    let bounded_params: Vec<Ident> = impl_generics
+        .type_params()
+        .map(|tp| tp.ident.clone())
+        .filter(|ident| used.contains(ident))
+        .collect();
+    for ident in bounded_params {
+        impl_generics
+            .make_where_clause()
+            .predicates
+            .push(parse_quote! { #ident: ::serde::Deserialize<'de> });
+    }
+
+    (impl_generics, type_generics)
+}
@@ -0,0 +1,68 @@
+use std::cell::RefCell;
+use std::fmt::Display;
+use std::thread;
+
+use quote::ToTokens;
+
+/// A context for accumulating errors discovered during attribute validation.
+///
+/// Modeled on serde_derive's `internals::Ctxt`: instead of bailing out at the
+/// first invalid variant, validation pushes one `syn::Error` per offending
+/// `Variant`/`Fields` into this context, each carrying that item's own span.
+/// The derive then folds everything collected via [`Ctxt::check`] into a
+/// single compile error so the user sees every mistake at once.
+pub(crate) struct Ctxt {
+    errors: RefCell<Option<Vec<syn::Error>>>,
+}
+
+impl Ctxt {
+    pub(crate) fn new() -> Self {
+        Ctxt {
+            errors: RefCell::new(Some(Vec::new())),
+        }
+    }
+
+    /// Records an error spanned at `obj` (typically a `Variant` or `Fields`).
+    pub(crate) fn error_spanned_by<T: ToTokens, U: Display>(&self, obj: T, msg: U) {
+        self.errors
+            .borrow_mut()
+            .as_mut()
+            .unwrap()
+            .push(syn::Error::new_spanned(obj.into_token_stream(), msg));
+    }
+
+    /// Records an already-built `syn::Error`, e.g. one returned by
+    /// `Attribute::parse_nested_meta`, which already carries its own span.
+    pub(crate) fn syn_error(&self, err: syn::Error) {
+        self.errors.borrow_mut().as_mut().unwrap().push(err);
+    }
+
+    /// Consumes the context, returning every error collected so far.
+    pub(crate) fn check(self) -> Result<(), Vec<syn::Error>> {
+        let errors = self.errors.borrow_mut().take().unwrap();
+        match errors.len() {
+            0 => Ok(()),
+            _ => Err(errors),
+        }
+    }
+}
+
+impl Drop for Ctxt {
+    fn drop(&mut self) {
+        if !thread::panicking() && self.errors.borrow().is_some() {
+            panic!("forgot to call Ctxt::check");
+        }
+    }
+}
+
+/// Folds every collected error into a single `syn::Error` (via
+/// `syn::Error::combine`) and renders it as a compile error, so all spans
+/// surface together in one compile pass.
+pub(crate) fn to_compile_errors(errors: Vec<syn::Error>) -> proc_macro2::TokenStream {
+    let mut errors = errors.into_iter();
+    let mut combined = errors.next().expect("to_compile_errors: no errors to report");
+    for error in errors {
+        combined.combine(error);
+    }
+    combined.to_compile_error()
+}
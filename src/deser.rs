@@ -2,10 +2,12 @@ use quote::quote;
 use syn::Fields;
 use syn::Ident;
 use syn::Index;
+use syn::Type;
 use syn::spanned::Spanned;
 
 use crate::VariantDiscriminant;
 use crate::VariantInfo;
+use crate::discriminant::cast_to_wire_type;
 
 fn generate_deserialize_fields(fields: &Fields) -> proc_macro2::TokenStream {
     match fields {
@@ -60,6 +62,7 @@ fn generate_variant_construction(
 pub(crate) fn generate_deserialization_branch(
     variant_info: &VariantInfo,
     name: &Ident,
+    discriminant_ty: &Type,
 ) -> proc_macro2::TokenStream {
     let variant_ident = &variant_info.variant.ident;
     let fields = &variant_info.variant.fields;
@@ -81,13 +84,11 @@ pub(crate) fn generate_deserialization_branch(
                         Ok(#name::#variant_ident(discriminant as #default_variant_ty))
                     }
                 }
-                None => {
-                    return syn::Error::new(
-                        variant_info.variant.span(),
-                        "Internal error: default_arm validation failed".to_string(),
-                    )
-                    .to_compile_error();
-                }
+                None => syn::Error::new(
+                    variant_info.variant.span(),
+                    "Internal error: default_arm validation failed".to_string(),
+                )
+                .to_compile_error(),
             }
         }
         VariantDiscriminant::Normal(discriminant_expr) => {
@@ -108,11 +109,58 @@ pub(crate) fn generate_deserialization_branch(
                 }
             };
 
+            let wire_discriminant = cast_to_wire_type(discriminant_expr, discriminant_ty);
             quote! {
-                if discriminant == (#discriminant_expr) as u32 {
+                // `.clone()` here, not a bare move: this comparison is
+                // emitted once per non-default variant, and comparing the
+                // place directly would move `discriminant` out on the first
+                // comparison whenever the discriminant_type isn't `Copy`
+                // (e.g. an enum path discriminant_type that only derives
+                // `Clone`), tripping E0382 on the next branch.
+                //
+                // `discriminant` is read as the container's own
+                // `discriminant_ty`, and the variant's discriminant
+                // expression goes through `cast_to_wire_type` at that same
+                // width instead of a hardcoded `u32`: coercing through `u32` would silently
+                // alias distinct discriminants of a wider type (e.g. `i64`)
+                // that only differ above bit 32.
+                if discriminant.clone() == (#wire_discriminant) {
                     return {#deserialization_body};
                 }
             }
         }
     }
 }
+
+/// Generates the externally-tagged branch used when
+/// `deserializer.is_human_readable()`: dispatches on the variant's
+/// identifier string read as the single map key, then deserializes the
+/// payload from the corresponding map value.
+pub(crate) fn generate_map_deserialization_branch(
+    variant_info: &VariantInfo,
+    name: &Ident,
+) -> proc_macro2::TokenStream {
+    let variant_ident = &variant_info.variant.ident;
+    let variant_name = variant_ident.to_string();
+    let fields = &variant_info.variant.fields;
+    let variant_construction = generate_variant_construction(variant_ident, fields, name);
+
+    let deserialization_body = if matches!(fields, Fields::Unit) {
+        quote! {
+            map.next_value::<()>()?;
+            Ok(#variant_construction)
+        }
+    } else {
+        let field_types = generate_deserialize_fields(fields);
+        quote! {
+            let fields = map.next_value::#field_types()?;
+            Ok(#variant_construction)
+        }
+    };
+
+    quote! {
+        if key == #variant_name {
+            return { #deserialization_body };
+        }
+    }
+}
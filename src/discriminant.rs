@@ -0,0 +1,72 @@
+use proc_macro2::TokenStream;
+use quote::{ToTokens, quote};
+use syn::Type;
+
+const PRIMITIVE_INTS: &[&str] = &[
+    "i8", "i16", "i32", "i64", "i128", "isize", "u8", "u16", "u32", "u64", "u128", "usize",
+];
+
+pub(crate) fn is_bool(ty: &Type) -> bool {
+    matches!(ty, Type::Path(type_path) if type_path.qself.is_none() && type_path.path.is_ident("bool"))
+}
+
+pub(crate) fn is_primitive_int(ty: &Type) -> bool {
+    matches!(ty, Type::Path(type_path) if type_path.qself.is_none()
+        && type_path.path.segments.len() == 1
+        && PRIMITIVE_INTS.contains(&type_path.path.segments[0].ident.to_string().as_str()))
+}
+
+/// Whether `ty` is neither `bool` nor a built-in integer type, i.e. it names
+/// a user enum used as the wire discriminant type. Such a type has no
+/// general conversion from an arbitrary integer, so callers need to know
+/// when a variant's discriminant expression must already be of type `ty`.
+pub(crate) fn is_enum_path(ty: &Type) -> bool {
+    !is_bool(ty) && !is_primitive_int(ty)
+}
+
+/// Whether `ty` is a bare type path with no generic arguments, e.g. `Kind`
+/// or `some_module::Kind`, as opposed to `Vec<u8>`, `&str`, `(u8, u8)`, or
+/// `[u8; 4]`. `bool` and the built-in integers are bare paths too.
+///
+/// This is the syntactic shape every legal `discriminant_type` has: `bool`
+/// and the integer widths are single-segment idents, and a user enum is
+/// named the same way a type is named anywhere else in Rust. Types with
+/// generic arguments, references, tuples, and arrays can never satisfy the
+/// `as`-cast or unchanged-passthrough that [`cast_to_wire_type`] emits, so
+/// rejecting them at attribute-parse time turns a confusing error deep in
+/// the derive's generated code into a clear one on the attribute itself.
+fn is_bare_type_path(ty: &Type) -> bool {
+    matches!(ty, Type::Path(type_path) if type_path.qself.is_none()
+        && type_path.path.segments.iter().all(|segment| segment.arguments.is_none()))
+}
+
+/// Whether `ty` is a legal `#[xdr(discriminant_type = "...")]`: `bool`, a
+/// built-in integer, or a bare path naming a user enum. RFC 4506 only
+/// specifies `int`/`unsigned int`/`bool`/enum (i.e. `i32`/`u32`/`bool`/enum),
+/// but this derive also accepts the other integer widths in
+/// [`PRIMITIVE_INTS`] since [`cast_to_wire_type`] already handles them
+/// correctly for any width.
+pub(crate) fn is_valid_discriminant_type(ty: &Type) -> bool {
+    is_bool(ty) || is_primitive_int(ty) || is_bare_type_path(ty)
+}
+
+/// Converts a discriminant expression into a value of the wire
+/// `discriminant_type` (see `#[xdr(discriminant_type = "...")]`), so it can
+/// be handed to `serialize_element`.
+///
+/// For `bool` and the built-in integer types the expression is normally a
+/// plain integer produced by the implicit 0, 1, 2, ... numbering: for `bool`
+/// it is cast to `u32` and compared against zero, and for an integer type it
+/// is cast directly to that type. For an enumerated discriminant type the
+/// expression is expected to already name a value of that enum (e.g.
+/// `#[xdr(discriminant = Kind::A)]`), since there is no general conversion
+/// from an arbitrary integer to an arbitrary enum.
+pub(crate) fn cast_to_wire_type<T: ToTokens>(discriminant: &T, ty: &Type) -> TokenStream {
+    if is_bool(ty) {
+        quote! { (((#discriminant) as u32) != 0) }
+    } else if is_primitive_int(ty) {
+        quote! { ((#discriminant) as #ty) }
+    } else {
+        quote! { (#discriminant) }
+    }
+}
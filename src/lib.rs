@@ -1,13 +1,28 @@
 use proc_macro::TokenStream;
 use quote::quote;
-use syn::{Data, DeriveInput, Expr, Fields, Variant, parse_quote};
+use syn::{Data, DeriveInput, Expr, Fields, Type, Variant, parse_quote};
 
-use crate::deser::generate_deserialization_branch;
-use crate::ser::generate_match_arm;
+use crate::attr::{ContainerAttrs, parse_container_attrs, parse_variant_attrs};
+use crate::ctxt::{Ctxt, to_compile_errors};
+use crate::deser::{generate_deserialization_branch, generate_map_deserialization_branch};
+use crate::ser::{generate_human_readable_match_arm, generate_match_arm, generate_skip_match_arm};
 
+mod attr;
+mod bound;
+mod ctxt;
 mod deser;
+mod discriminant;
 mod ser;
 
+/// The wire discriminant type (`#[xdr(discriminant_type = "...")]`), or the
+/// default `u32` when the container didn't specify one.
+fn discriminant_type(container_attrs: &ContainerAttrs) -> Type {
+    container_attrs
+        .discriminant_type
+        .clone()
+        .unwrap_or_else(|| parse_quote! { u32 })
+}
+
 #[derive(Clone)]
 enum VariantDiscriminant {
     Normal(Expr),
@@ -20,23 +35,90 @@ struct VariantInfo<'a> {
 }
 
 fn calculate_variant_discriminants<'a>(
+    cx: &Ctxt,
     variants: impl IntoIterator<Item = &'a Variant>,
-) -> Result<Vec<VariantInfo<'a>>, String> {
+    discriminant_ty: &Type,
+) -> (Vec<VariantInfo<'a>>, Vec<&'a Variant>) {
     let mut result = Vec::new();
+    let mut skipped = Vec::new();
     let mut next_discriminant: Expr = parse_quote! { 0 };
     let mut has_default = false;
+    let mut implicit_bool_count = 0usize;
+    let mut saw_explicit_override = false;
     for v in variants {
-        let discriminant = if has_default_attribute(v) {
+        let attrs = parse_variant_attrs(cx, v);
+
+        if attrs.skip {
+            skipped.push(v);
+            continue;
+        }
+
+        let discriminant = if attrs.default {
             if has_default {
-                return Err("Only one default arm is allowed".to_string());
+                cx.error_spanned_by(v, "only one default arm is allowed");
+            }
+            if let Err(e) = validate_default_arm_fields(&v.fields) {
+                match &v.fields {
+                    // A unit variant has no field tokens to point at.
+                    Fields::Unit => cx.error_spanned_by(v, e),
+                    fields => cx.error_spanned_by(fields, e),
+                }
+            }
+            if discriminant::is_enum_path(discriminant_ty) {
+                cx.error_spanned_by(
+                    v,
+                    "`#[xdr(default)]` cannot be combined with an enum-path discriminant_type: \
+                     the field it would receive the unmatched discriminant cast into has no \
+                     general conversion from an arbitrary enum value, and a typical \
+                     `Deserialize` impl for that enum already rejects unrecognized wire values \
+                     before this arm could ever run",
+                );
             }
-            validate_default_arm_fields(&v.fields)?;
             has_default = true;
             VariantDiscriminant::Default
+        } else if let Some(expr) = attrs.discriminant {
+            if discriminant::is_primitive_int(discriminant_ty) {
+                next_discriminant = parse_quote! { (#expr + 1) };
+            } else {
+                saw_explicit_override = true;
+            }
+            VariantDiscriminant::Normal(expr)
         } else {
+            if discriminant::is_enum_path(discriminant_ty) {
+                cx.error_spanned_by(
+                    v,
+                    "variants must have an explicit `#[xdr(discriminant = ...)]` of the \
+                     container's discriminant_type when that type is not `bool` or a built-in \
+                     integer, since there is no general way to number enum values implicitly",
+                );
+            }
+            if discriminant::is_bool(discriminant_ty) {
+                implicit_bool_count += 1;
+                if implicit_bool_count > 2 {
+                    cx.error_spanned_by(
+                        v,
+                        "variants must have an explicit `#[xdr(discriminant = ...)]` once more \
+                         than two variants share a `bool` discriminant_type, since implicit 0, \
+                         1, 2, ... numbering collapses to only `false`/`true` on the wire",
+                    );
+                }
+                if saw_explicit_override {
+                    cx.error_spanned_by(
+                        v,
+                        "variants must have an explicit `#[xdr(discriminant = ...)]` once an \
+                         earlier variant overrides its discriminant under a `bool` \
+                         discriminant_type, since implicit numbering cannot resume from an \
+                         arbitrary bool value",
+                    );
+                }
+            }
             let current = match &v.discriminant {
                 Some((_, expr)) => {
-                    next_discriminant = parse_quote! { (#expr + 1) };
+                    if discriminant::is_primitive_int(discriminant_ty) {
+                        next_discriminant = parse_quote! { (#expr + 1) };
+                    } else {
+                        saw_explicit_override = true;
+                    }
                     expr.clone()
                 }
                 None => {
@@ -53,24 +135,19 @@ fn calculate_variant_discriminants<'a>(
             variant: v,
         });
     }
-    Ok(result)
-}
-
-fn has_default_attribute(variant: &Variant) -> bool {
-    variant
-        .attrs
-        .iter()
-        .any(|attr| attr.path().is_ident("default_arm"))
+    (result, skipped)
 }
 
 fn validate_default_arm_fields(fields: &Fields) -> Result<(), String> {
     match fields {
         Fields::Unnamed(unnamed) if unnamed.unnamed.len() == 1 => Ok(()),
-        _ => Err("Default arms must have exactly one unnamed field of type u32".to_string()),
+        _ => Err("Default arms must have exactly one unnamed field, which receives the \
+                  unmatched discriminant cast to that field's type"
+            .to_string()),
     }
 }
 
-#[proc_macro_derive(XDREnumSerialize, attributes(default_arm))]
+#[proc_macro_derive(XDREnumSerialize, attributes(xdr))]
 pub fn derive_xdr_enum_serialize(input: TokenStream) -> TokenStream {
     let ast = syn::parse_macro_input!(input as DeriveInput);
     let name = &ast.ident;
@@ -87,26 +164,45 @@ pub fn derive_xdr_enum_serialize(input: TokenStream) -> TokenStream {
         }
     };
 
-    let variant_infos = match calculate_variant_discriminants(variants) {
-        Ok(v) => v,
-        Err(e) => {
-            return syn::Error::new(ast.ident.span(), e)
-                .to_compile_error()
-                .into();
-        }
-    };
+    let cx = Ctxt::new();
+    let container_attrs = parse_container_attrs(&cx, &ast.attrs);
+    let discriminant_ty = discriminant_type(&container_attrs);
+    let (variant_infos, skipped_variants) =
+        calculate_variant_discriminants(&cx, variants.clone(), &discriminant_ty);
+    if let Err(errors) = cx.check() {
+        return to_compile_errors(errors).into();
+    }
 
-    let match_arms = variant_infos.iter().map(generate_match_arm);
+    let generics =
+        bound::with_serialize_bound(&ast.generics, &variants, container_attrs.bound.as_deref());
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+
+    let match_arms = variant_infos
+        .iter()
+        .map(|vi| generate_match_arm(vi, &discriminant_ty));
+    let human_readable_match_arms = variant_infos.iter().map(generate_human_readable_match_arm);
+    let skip_match_arms: Vec<_> = skipped_variants
+        .iter()
+        .map(|v| generate_skip_match_arm(v))
+        .collect();
 
     let expanded = quote! {
         const _: () = {
-            impl ::serde::Serialize for #name {
+            impl #impl_generics ::serde::Serialize for #name #ty_generics #where_clause {
                 fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
                 where
                     S: ::serde::Serializer,
                 {
-                    match self {
-                        #(#match_arms)*
+                    if serializer.is_human_readable() {
+                        match self {
+                            #(#human_readable_match_arms)*
+                            #(#skip_match_arms)*
+                        }
+                    } else {
+                        match self {
+                            #(#match_arms)*
+                            #(#skip_match_arms)*
+                        }
                     }
                 }
             }
@@ -116,7 +212,7 @@ pub fn derive_xdr_enum_serialize(input: TokenStream) -> TokenStream {
     expanded.into()
 }
 
-#[proc_macro_derive(XDREnumDeserialize, attributes(default_arm))]
+#[proc_macro_derive(XDREnumDeserialize, attributes(xdr))]
 pub fn derive_xdr_enum_deserialize(input: TokenStream) -> TokenStream {
     let ast = syn::parse_macro_input!(input as DeriveInput);
     let name = &ast.ident;
@@ -133,14 +229,27 @@ pub fn derive_xdr_enum_deserialize(input: TokenStream) -> TokenStream {
         }
     };
 
-    let variant_infos = match calculate_variant_discriminants(variants) {
-        Ok(infos) => infos,
-        Err(error) => {
-            return syn::Error::new(ast.ident.span(), error)
-                .to_compile_error()
-                .into();
-        }
-    };
+    let cx = Ctxt::new();
+    let container_attrs = parse_container_attrs(&cx, &ast.attrs);
+    let discriminant_ty = discriminant_type(&container_attrs);
+    let (variant_infos, _skipped_variants) =
+        calculate_variant_discriminants(&cx, variants.clone(), &discriminant_ty);
+    if let Err(errors) = cx.check() {
+        return to_compile_errors(errors).into();
+    }
+
+    let (impl_generics_src, type_generics_src) = bound::deserialize_generics(
+        &ast.generics,
+        &variants,
+        container_attrs.bound.as_deref(),
+    );
+    let (impl_generics, _, where_clause) = impl_generics_src.split_for_impl();
+    let (_, ty_generics, _) = type_generics_src.split_for_impl();
+
+    let type_param_idents: Vec<syn::Ident> =
+        ast.generics.type_params().map(|tp| tp.ident.clone()).collect();
+    let lifetime_idents: Vec<syn::Lifetime> =
+        ast.generics.lifetimes().map(|lp| lp.lifetime.clone()).collect();
 
     let (normal_branches, default_branch): (Vec<_>, Vec<_>) = variant_infos
         .iter()
@@ -148,53 +257,130 @@ pub fn derive_xdr_enum_deserialize(input: TokenStream) -> TokenStream {
 
     let normal_deserialization_branches = normal_branches
         .iter()
-        .map(|vi| generate_deserialization_branch(vi, name));
+        .map(|vi| generate_deserialization_branch(vi, name, &discriminant_ty));
 
     let default_handling = if let Some(default_variant) = default_branch.first() {
-        generate_deserialization_branch(default_variant, name)
+        generate_deserialization_branch(default_variant, name, &discriminant_ty)
     } else {
         quote! {
             return Err(::serde::de::Error::custom(format!(
-                "Unknown discriminant {} for enum {}",
+                "Unknown discriminant {:?} for enum {}",
                 discriminant, stringify!(#name)
             )));
         }
     };
 
-    let visitor_struct_defs = quote! {
-        struct __Visitor;
-
-        impl<'de> ::serde::de::Visitor<'de> for __Visitor {
-            type Value = #name;
-
-            fn expecting(&self, formatter: &mut ::std::fmt::Formatter) -> ::std::fmt::Result {
-                formatter.write_str(concat!("enum ", stringify!(#name)))
-            }
+    let human_readable_branches = variant_infos
+        .iter()
+        .map(|vi| generate_map_deserialization_branch(vi, name));
 
-            fn visit_seq<A>(self, mut data: A) -> Result<Self::Value, A::Error>
-            where
-                A: ::serde::de::SeqAccess<'de>,
-            {
-                let discriminant: u32 = data.next_element()?
-                    .ok_or_else(|| ::serde::de::Error::invalid_length(0, &self))?;
+    let visit_map_method = quote! {
+        fn visit_map<A>(self, mut map: A) -> Result<Self::Value, A::Error>
+        where
+            A: ::serde::de::MapAccess<'de>,
+        {
+            let key: String = map.next_key()?
+                .ok_or_else(|| ::serde::de::Error::invalid_length(0, &self))?;
 
-                #(#normal_deserialization_branches)*
+            #(#human_readable_branches)*
 
-                #default_handling
-            }
+            // Unlike `visit_seq`, this never falls back to `#default_handling`:
+            // the `#[xdr(default)]` variant exists to receive an *unmatched
+            // numeric discriminant* cast to its field type, and the
+            // externally-tagged human-readable form has no such value to
+            // give it, only the unrecognized key itself. An unrecognized key
+            // is therefore always an error here, even when the enum declares
+            // a default variant.
+            Err(::serde::de::Error::custom(format!(
+                "Unknown variant {} for enum {}",
+                key, stringify!(#name)
+            )))
         }
     };
 
+    let (visitor_struct_defs, visitor_construction) =
+        if type_param_idents.is_empty() && lifetime_idents.is_empty() {
+            let defs = quote! {
+                struct __Visitor;
+
+                impl #impl_generics ::serde::de::Visitor<'de> for __Visitor #where_clause {
+                    type Value = #name #ty_generics;
+
+                    fn expecting(&self, formatter: &mut ::std::fmt::Formatter) -> ::std::fmt::Result {
+                        formatter.write_str(concat!("enum ", stringify!(#name)))
+                    }
+
+                    fn visit_seq<A>(self, mut data: A) -> Result<Self::Value, A::Error>
+                    where
+                        A: ::serde::de::SeqAccess<'de>,
+                    {
+                        let discriminant: #discriminant_ty = data.next_element()?
+                            .ok_or_else(|| ::serde::de::Error::invalid_length(0, &self))?;
+
+                        #(#normal_deserialization_branches)*
+
+                        #default_handling
+                    }
+
+                    #visit_map_method
+                }
+            };
+            (defs, quote! { __Visitor })
+        } else {
+            // The enum's own lifetime parameters (if any) have to be threaded
+            // through `__Visitor` too, not just its type parameters: they're
+            // still present in `impl_generics`, and a lifetime that appears
+            // there but not in the impl's self type trips E0207 ("lifetime
+            // parameter is not constrained").
+            let visitor_generics = quote! { <#(#lifetime_idents,)* #(#type_param_idents),*> };
+            let marker_ty = quote! { (#(&#lifetime_idents (),)* #(#type_param_idents,)*) };
+            let defs = quote! {
+                struct __Visitor #visitor_generics {
+                    marker: ::std::marker::PhantomData<#marker_ty>,
+                }
+
+                impl #impl_generics ::serde::de::Visitor<'de> for __Visitor #visitor_generics #where_clause {
+                    type Value = #name #ty_generics;
+
+                    fn expecting(&self, formatter: &mut ::std::fmt::Formatter) -> ::std::fmt::Result {
+                        formatter.write_str(concat!("enum ", stringify!(#name)))
+                    }
+
+                    fn visit_seq<A>(self, mut data: A) -> Result<Self::Value, A::Error>
+                    where
+                        A: ::serde::de::SeqAccess<'de>,
+                    {
+                        let discriminant: #discriminant_ty = data.next_element()?
+                            .ok_or_else(|| ::serde::de::Error::invalid_length(0, &self))?;
+
+                        #(#normal_deserialization_branches)*
+
+                        #default_handling
+                    }
+
+                    #visit_map_method
+                }
+            };
+            (
+                defs,
+                quote! { __Visitor { marker: ::std::marker::PhantomData } },
+            )
+        };
+
     let expanded = quote! {
         const _: () = {
             #visitor_struct_defs
 
-            impl<'de> ::serde::Deserialize<'de> for #name {
+            impl #impl_generics ::serde::Deserialize<'de> for #name #ty_generics #where_clause {
                 fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
                 where
                     D: ::serde::Deserializer<'de>,
                 {
-                    deserializer.deserialize_tuple(2, __Visitor)
+                    if deserializer.is_human_readable() {
+                        deserializer.deserialize_map(#visitor_construction)
+                    } else {
+                        deserializer.deserialize_tuple(2, #visitor_construction)
+                    }
                 }
             }
         };
@@ -202,3 +388,67 @@ pub fn derive_xdr_enum_deserialize(input: TokenStream) -> TokenStream {
 
     expanded.into()
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Two variants that each lack a required `#[xdr(discriminant = ...)]`
+    /// under an enum-path `discriminant_type` should both be flagged by
+    /// `calculate_variant_discriminants`, not just the first: validation
+    /// accumulates one error per offending variant into `cx` instead of
+    /// bailing out as soon as it finds one.
+    #[test]
+    fn accumulates_one_error_per_invalid_variant_without_bailing_early() {
+        let ast: DeriveInput = parse_quote! {
+            enum Foo {
+                A(u32),
+                B(u32),
+            }
+        };
+        let variants = match &ast.data {
+            Data::Enum(data) => data.variants.iter().collect::<Vec<_>>(),
+            _ => unreachable!(),
+        };
+
+        let cx = Ctxt::new();
+        let discriminant_ty: Type = parse_quote! { SomeEnumPath };
+        let _ = calculate_variant_discriminants(&cx, variants, &discriminant_ty);
+        let errors = cx
+            .check()
+            .expect_err("both implicit variants should be flagged under an enum-path discriminant_type");
+
+        assert_eq!(
+            errors.len(),
+            2,
+            "expected one accumulated error per invalid variant, not just the first"
+        );
+    }
+
+    /// `#[xdr(default)]` under an enum-path `discriminant_type` must be
+    /// rejected: the default arm's field has no general conversion from an
+    /// arbitrary enum value, and a typical `Deserialize` impl for that enum
+    /// already rejects unrecognized wire values before the default arm
+    /// could ever run, so the combination is always either broken or dead.
+    #[test]
+    fn rejects_default_arm_under_enum_path_discriminant_type() {
+        let ast: DeriveInput = parse_quote! {
+            enum Foo {
+                #[xdr(discriminant = SomeEnumPath::A)]
+                A(u32),
+                #[xdr(default)]
+                Unknown(u32),
+            }
+        };
+        let variants = match &ast.data {
+            Data::Enum(data) => data.variants.iter().collect::<Vec<_>>(),
+            _ => unreachable!(),
+        };
+
+        let cx = Ctxt::new();
+        let discriminant_ty: Type = parse_quote! { SomeEnumPath };
+        let _ = calculate_variant_discriminants(&cx, variants, &discriminant_ty);
+        cx.check()
+            .expect_err("`#[xdr(default)]` combined with an enum-path discriminant_type should be rejected");
+    }
+}
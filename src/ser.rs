@@ -1,6 +1,7 @@
 use quote::{format_ident, quote};
-use syn::{Fields, Ident};
+use syn::{Fields, Ident, Type, Variant};
 
+use crate::discriminant::cast_to_wire_type;
 use crate::{VariantDiscriminant, VariantInfo};
 
 pub(crate) fn generate_field_bindings(fields: &Fields) -> (Vec<Ident>, proc_macro2::TokenStream) {
@@ -29,17 +30,60 @@ pub(crate) fn generate_serialize_element(bindings: &[Ident]) -> proc_macro2::Tok
     quote! { &(#(#bindings,)*) }
 }
 
-pub(crate) fn generate_match_arm(variant_info: &VariantInfo) -> proc_macro2::TokenStream {
+/// Generates the externally-tagged match arm used when
+/// `serializer.is_human_readable()`: a single-entry map keyed by the
+/// variant's identifier string, with the variant payload as the value.
+pub(crate) fn generate_human_readable_match_arm(
+    variant_info: &VariantInfo,
+) -> proc_macro2::TokenStream {
+    let variant_ident = &variant_info.variant.ident;
+    let variant_name = variant_ident.to_string();
+    let (field_bindings, field_pattern) = generate_field_bindings(&variant_info.variant.fields);
+    let serialize_element = generate_serialize_element(&field_bindings);
+
+    quote! {
+        Self::#variant_ident #field_pattern => {
+            let mut map = serializer.serialize_map(Some(1))?;
+            ::serde::ser::SerializeMap::serialize_entry(&mut map, #variant_name, #serialize_element)?;
+            ::serde::ser::SerializeMap::end(map)
+        }
+    }
+}
+
+/// Generates the fallback arm for a `#[xdr(skip)]` variant: it still exists
+/// on the real enum, so the generated `match self { ... }` needs an arm for
+/// it to stay exhaustive, even though skipped variants have no wire form.
+pub(crate) fn generate_skip_match_arm(variant: &Variant) -> proc_macro2::TokenStream {
+    let variant_ident = &variant.ident;
+    let variant_name = variant_ident.to_string();
+    let pattern = match &variant.fields {
+        Fields::Unit => quote! { Self::#variant_ident },
+        Fields::Unnamed(_) => quote! { Self::#variant_ident(..) },
+        Fields::Named(_) => quote! { Self::#variant_ident { .. } },
+    };
+
+    quote! {
+        #pattern => Err(::serde::ser::Error::custom(concat!(
+            "cannot serialize variant marked `#[xdr(skip)]`: ", #variant_name
+        ))),
+    }
+}
+
+pub(crate) fn generate_match_arm(
+    variant_info: &VariantInfo,
+    discriminant_ty: &Type,
+) -> proc_macro2::TokenStream {
     let variant_ident = &variant_info.variant.ident;
     let (field_bindings, field_pattern) = generate_field_bindings(&variant_info.variant.fields);
 
     match &variant_info.discriminant {
         VariantDiscriminant::Default => {
             let field_name = &field_bindings[0];
+            let wire_discriminant = cast_to_wire_type(&quote! { *#field_name }, discriminant_ty);
             quote! {
                 Self::#variant_ident #field_pattern => {
                     let mut ser = serializer.serialize_tuple(2)?;
-                    ::serde::ser::SerializeTuple::serialize_element(&mut ser, &(*#field_name as u32))?;
+                    ::serde::ser::SerializeTuple::serialize_element(&mut ser, &#wire_discriminant)?;
                     ::serde::ser::SerializeTuple::serialize_element(&mut ser, &())?;
                     ::serde::ser::SerializeTuple::end(ser)
                 }
@@ -47,10 +91,11 @@ pub(crate) fn generate_match_arm(variant_info: &VariantInfo) -> proc_macro2::Tok
         }
         VariantDiscriminant::Normal(discriminant) => {
             let serialize_element = generate_serialize_element(&field_bindings);
+            let wire_discriminant = cast_to_wire_type(discriminant, discriminant_ty);
             quote! {
                 Self::#variant_ident #field_pattern => {
                     let mut ser = serializer.serialize_tuple(2)?;
-                    ::serde::ser::SerializeTuple::serialize_element(&mut ser, &((#discriminant) as u32))?;
+                    ::serde::ser::SerializeTuple::serialize_element(&mut ser, &#wire_discriminant)?;
                     ::serde::ser::SerializeTuple::serialize_element(&mut ser, #serialize_element)?;
                     ::serde::ser::SerializeTuple::end(ser)
                 }
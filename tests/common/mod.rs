@@ -0,0 +1,404 @@
+//! Minimal hand-rolled `Serializer`/`Deserializer` pair used by the
+//! integration tests to exercise round trips without depending on an actual
+//! wire-format crate. The serializer implements tuples, maps, and the scalar
+//! types used in the test enums, and errors out on floats, bytes, sequences,
+//! structs, and non-unit enum variants; the deserializer handles tuples and
+//! maps itself and forwards every other request to `deserialize_any`.
+#![allow(dead_code)]
+
+use std::fmt;
+
+use serde::de::{self, Deserialize, Deserializer, MapAccess, SeqAccess, Visitor};
+use serde::ser::{self, Serialize, SerializeMap, SerializeTuple, Serializer};
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Value {
+    Unit,
+    Bool(bool),
+    U8(u8),
+    U32(u32),
+    I32(i32),
+    Str(String),
+    Seq(Vec<Value>),
+    Map(Vec<(Value, Value)>),
+}
+
+#[derive(Debug)]
+pub struct Error(String);
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl std::error::Error for Error {}
+
+impl Error {
+    fn unsupported(what: &str) -> Self {
+        Error(format!("unsupported in test serializer/deserializer: {what}"))
+    }
+}
+
+impl ser::Error for Error {
+    fn custom<T: fmt::Display>(msg: T) -> Self {
+        Error(msg.to_string())
+    }
+}
+
+impl de::Error for Error {
+    fn custom<T: fmt::Display>(msg: T) -> Self {
+        Error(msg.to_string())
+    }
+}
+
+pub fn to_value<T: Serialize + ?Sized>(value: &T, human_readable: bool) -> Value {
+    value
+        .serialize(ValueSerializer { human_readable })
+        .expect("serialize into test Value failed")
+}
+
+pub fn from_value<'de, T: Deserialize<'de>>(value: Value, human_readable: bool) -> T {
+    T::deserialize(ValueDeserializer::new(value, human_readable))
+        .expect("deserialize from test Value failed")
+}
+
+pub struct ValueSerializer {
+    pub human_readable: bool,
+}
+
+pub struct TupleSerializer {
+    items: Vec<Value>,
+    human_readable: bool,
+}
+
+impl SerializeTuple for TupleSerializer {
+    type Ok = Value;
+    type Error = Error;
+
+    fn serialize_element<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), Error> {
+        self.items
+            .push(value.serialize(ValueSerializer { human_readable: self.human_readable })?);
+        Ok(())
+    }
+
+    fn end(self) -> Result<Value, Error> {
+        Ok(Value::Seq(self.items))
+    }
+}
+
+pub struct MapSerializerImpl {
+    items: Vec<(Value, Value)>,
+    human_readable: bool,
+    pending_key: Option<Value>,
+}
+
+impl SerializeMap for MapSerializerImpl {
+    type Ok = Value;
+    type Error = Error;
+
+    fn serialize_key<T: Serialize + ?Sized>(&mut self, key: &T) -> Result<(), Error> {
+        self.pending_key = Some(key.serialize(ValueSerializer { human_readable: self.human_readable })?);
+        Ok(())
+    }
+
+    fn serialize_value<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), Error> {
+        let key = self
+            .pending_key
+            .take()
+            .expect("serialize_value called before serialize_key");
+        let value = value.serialize(ValueSerializer { human_readable: self.human_readable })?;
+        self.items.push((key, value));
+        Ok(())
+    }
+
+    fn end(self) -> Result<Value, Error> {
+        Ok(Value::Map(self.items))
+    }
+}
+
+impl Serializer for ValueSerializer {
+    type Ok = Value;
+    type Error = Error;
+    type SerializeSeq = ser::Impossible<Value, Error>;
+    type SerializeTuple = TupleSerializer;
+    type SerializeTupleStruct = ser::Impossible<Value, Error>;
+    type SerializeTupleVariant = ser::Impossible<Value, Error>;
+    type SerializeMap = MapSerializerImpl;
+    type SerializeStruct = ser::Impossible<Value, Error>;
+    type SerializeStructVariant = ser::Impossible<Value, Error>;
+
+    fn is_human_readable(&self) -> bool {
+        self.human_readable
+    }
+
+    fn serialize_bool(self, v: bool) -> Result<Value, Error> {
+        Ok(Value::Bool(v))
+    }
+
+    fn serialize_i8(self, v: i8) -> Result<Value, Error> {
+        Ok(Value::I32(v as i32))
+    }
+
+    fn serialize_i16(self, v: i16) -> Result<Value, Error> {
+        Ok(Value::I32(v as i32))
+    }
+
+    fn serialize_i32(self, v: i32) -> Result<Value, Error> {
+        Ok(Value::I32(v))
+    }
+
+    fn serialize_i64(self, v: i64) -> Result<Value, Error> {
+        Ok(Value::I32(v as i32))
+    }
+
+    fn serialize_u8(self, v: u8) -> Result<Value, Error> {
+        Ok(Value::U8(v))
+    }
+
+    fn serialize_u16(self, v: u16) -> Result<Value, Error> {
+        Ok(Value::U32(v as u32))
+    }
+
+    fn serialize_u32(self, v: u32) -> Result<Value, Error> {
+        Ok(Value::U32(v))
+    }
+
+    fn serialize_u64(self, v: u64) -> Result<Value, Error> {
+        Ok(Value::U32(v as u32))
+    }
+
+    fn serialize_f32(self, _v: f32) -> Result<Value, Error> {
+        Err(Error::unsupported("f32"))
+    }
+
+    fn serialize_f64(self, _v: f64) -> Result<Value, Error> {
+        Err(Error::unsupported("f64"))
+    }
+
+    fn serialize_char(self, v: char) -> Result<Value, Error> {
+        Ok(Value::Str(v.to_string()))
+    }
+
+    fn serialize_str(self, v: &str) -> Result<Value, Error> {
+        Ok(Value::Str(v.to_string()))
+    }
+
+    fn serialize_bytes(self, _v: &[u8]) -> Result<Value, Error> {
+        Err(Error::unsupported("bytes"))
+    }
+
+    fn serialize_none(self) -> Result<Value, Error> {
+        Ok(Value::Unit)
+    }
+
+    fn serialize_some<T: Serialize + ?Sized>(self, value: &T) -> Result<Value, Error> {
+        value.serialize(self)
+    }
+
+    fn serialize_unit(self) -> Result<Value, Error> {
+        Ok(Value::Unit)
+    }
+
+    fn serialize_unit_struct(self, _name: &'static str) -> Result<Value, Error> {
+        Ok(Value::Unit)
+    }
+
+    fn serialize_unit_variant(
+        self,
+        _name: &'static str,
+        _index: u32,
+        variant: &'static str,
+    ) -> Result<Value, Error> {
+        Ok(Value::Str(variant.to_string()))
+    }
+
+    fn serialize_newtype_struct<T: Serialize + ?Sized>(
+        self,
+        _name: &'static str,
+        value: &T,
+    ) -> Result<Value, Error> {
+        value.serialize(self)
+    }
+
+    fn serialize_newtype_variant<T: Serialize + ?Sized>(
+        self,
+        _name: &'static str,
+        _index: u32,
+        _variant: &'static str,
+        _value: &T,
+    ) -> Result<Value, Error> {
+        Err(Error::unsupported("newtype_variant"))
+    }
+
+    fn serialize_seq(self, _len: Option<usize>) -> Result<Self::SerializeSeq, Error> {
+        Err(Error::unsupported("seq"))
+    }
+
+    fn serialize_tuple(self, len: usize) -> Result<Self::SerializeTuple, Error> {
+        Ok(TupleSerializer {
+            items: Vec::with_capacity(len),
+            human_readable: self.human_readable,
+        })
+    }
+
+    fn serialize_tuple_struct(
+        self,
+        _name: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeTupleStruct, Error> {
+        Err(Error::unsupported("tuple_struct"))
+    }
+
+    fn serialize_tuple_variant(
+        self,
+        _name: &'static str,
+        _index: u32,
+        _variant: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeTupleVariant, Error> {
+        Err(Error::unsupported("tuple_variant"))
+    }
+
+    fn serialize_map(self, _len: Option<usize>) -> Result<Self::SerializeMap, Error> {
+        Ok(MapSerializerImpl {
+            items: Vec::new(),
+            human_readable: self.human_readable,
+            pending_key: None,
+        })
+    }
+
+    fn serialize_struct(self, _name: &'static str, _len: usize) -> Result<Self::SerializeStruct, Error> {
+        Err(Error::unsupported("struct"))
+    }
+
+    fn serialize_struct_variant(
+        self,
+        _name: &'static str,
+        _index: u32,
+        _variant: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeStructVariant, Error> {
+        Err(Error::unsupported("struct_variant"))
+    }
+}
+
+pub struct ValueDeserializer {
+    value: Value,
+    human_readable: bool,
+}
+
+impl ValueDeserializer {
+    pub fn new(value: Value, human_readable: bool) -> Self {
+        ValueDeserializer { value, human_readable }
+    }
+}
+
+impl<'de> Deserializer<'de> for ValueDeserializer {
+    type Error = Error;
+
+    fn is_human_readable(&self) -> bool {
+        self.human_readable
+    }
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        match self.value {
+            Value::Unit => visitor.visit_unit(),
+            Value::Bool(b) => visitor.visit_bool(b),
+            Value::U8(n) => visitor.visit_u8(n),
+            Value::U32(n) => visitor.visit_u32(n),
+            Value::I32(n) => visitor.visit_i32(n),
+            Value::Str(s) => visitor.visit_string(s),
+            Value::Seq(items) => visitor.visit_seq(ValueSeqAccess::new(items, self.human_readable)),
+            Value::Map(items) => visitor.visit_map(ValueMapAccess::new(items, self.human_readable)),
+        }
+    }
+
+    fn deserialize_tuple<V: Visitor<'de>>(self, _len: usize, visitor: V) -> Result<V::Value, Error> {
+        match self.value {
+            Value::Seq(items) => visitor.visit_seq(ValueSeqAccess::new(items, self.human_readable)),
+            other => Err(<Error as de::Error>::custom(format!(
+                "expected sequence, found {other:?}"
+            ))),
+        }
+    }
+
+    fn deserialize_map<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        match self.value {
+            Value::Map(items) => visitor.visit_map(ValueMapAccess::new(items, self.human_readable)),
+            other => Err(<Error as de::Error>::custom(format!(
+                "expected map, found {other:?}"
+            ))),
+        }
+    }
+
+    serde::forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 u8 u16 u32 u64 f32 f64 char str string bytes byte_buf
+        option unit unit_struct newtype_struct seq tuple_struct struct enum identifier ignored_any
+    }
+}
+
+pub struct ValueSeqAccess {
+    iter: std::vec::IntoIter<Value>,
+    human_readable: bool,
+}
+
+impl ValueSeqAccess {
+    fn new(items: Vec<Value>, human_readable: bool) -> Self {
+        ValueSeqAccess { iter: items.into_iter(), human_readable }
+    }
+}
+
+impl<'de> SeqAccess<'de> for ValueSeqAccess {
+    type Error = Error;
+
+    fn next_element_seed<T: de::DeserializeSeed<'de>>(
+        &mut self,
+        seed: T,
+    ) -> Result<Option<T::Value>, Error> {
+        match self.iter.next() {
+            Some(value) => seed
+                .deserialize(ValueDeserializer::new(value, self.human_readable))
+                .map(Some),
+            None => Ok(None),
+        }
+    }
+}
+
+pub struct ValueMapAccess {
+    iter: std::vec::IntoIter<(Value, Value)>,
+    pending_value: Option<Value>,
+    human_readable: bool,
+}
+
+impl ValueMapAccess {
+    fn new(items: Vec<(Value, Value)>, human_readable: bool) -> Self {
+        ValueMapAccess {
+            iter: items.into_iter(),
+            pending_value: None,
+            human_readable,
+        }
+    }
+}
+
+impl<'de> MapAccess<'de> for ValueMapAccess {
+    type Error = Error;
+
+    fn next_key_seed<K: de::DeserializeSeed<'de>>(&mut self, seed: K) -> Result<Option<K::Value>, Error> {
+        match self.iter.next() {
+            Some((k, v)) => {
+                self.pending_value = Some(v);
+                seed.deserialize(ValueDeserializer::new(k, self.human_readable)).map(Some)
+            }
+            None => Ok(None),
+        }
+    }
+
+    fn next_value_seed<V: de::DeserializeSeed<'de>>(&mut self, seed: V) -> Result<V::Value, Error> {
+        let value = self
+            .pending_value
+            .take()
+            .expect("next_value_seed called before next_key_seed");
+        seed.deserialize(ValueDeserializer::new(value, self.human_readable))
+    }
+}
@@ -0,0 +1,31 @@
+#[path = "common/mod.rs"]
+mod common;
+
+use xdr_brk_enum::{XDREnumDeserialize, XDREnumSerialize};
+
+#[derive(Debug, PartialEq, XDREnumSerialize, XDREnumDeserialize)]
+enum Code {
+    #[xdr(discriminant = 10)]
+    Ten,
+    Eleven,
+    #[xdr(discriminant = 100)]
+    Hundred(u32),
+    #[xdr(default)]
+    Unknown(u32),
+}
+
+#[test]
+fn round_trips_explicit_and_implicit_discriminants() {
+    for original in [Code::Ten, Code::Eleven, Code::Hundred(5)] {
+        let value = common::to_value(&original, false);
+        let restored: Code = common::from_value(value, false);
+        assert_eq!(original, restored);
+    }
+}
+
+#[test]
+fn falls_back_to_default_arm_for_unknown_discriminant() {
+    let value = common::Value::Seq(vec![common::Value::U32(999), common::Value::Unit]);
+    let restored: Code = common::from_value(value, false);
+    assert_eq!(restored, Code::Unknown(999));
+}
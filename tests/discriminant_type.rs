@@ -0,0 +1,149 @@
+#[path = "common/mod.rs"]
+mod common;
+
+use xdr_brk_enum::{XDREnumDeserialize, XDREnumSerialize};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Kind {
+    A,
+    B,
+}
+
+impl serde::Serialize for Kind {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let n: u32 = match self {
+            Kind::A => 0,
+            Kind::B => 1,
+        };
+        serializer.serialize_u32(n)
+    }
+}
+
+impl<'de> serde::Deserialize<'de> for Kind {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let n = u32::deserialize(deserializer)?;
+        match n {
+            0 => Ok(Kind::A),
+            1 => Ok(Kind::B),
+            other => Err(serde::de::Error::custom(format!("unknown Kind {other}"))),
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, XDREnumSerialize, XDREnumDeserialize)]
+#[xdr(discriminant_type = "Kind")]
+enum Tagged {
+    #[xdr(discriminant = Kind::A)]
+    First(u32),
+    #[xdr(discriminant = Kind::B)]
+    Second,
+}
+
+#[derive(Debug, PartialEq, XDREnumSerialize, XDREnumDeserialize)]
+#[xdr(discriminant_type = "bool")]
+enum Toggle {
+    Off,
+    On(u32),
+}
+
+// A `bool` discriminant_type with an explicit `#[xdr(discriminant = ...)]`
+// override on one variant followed by an *explicit* override on the next,
+// rather than implicit numbering. Implicit numbering can't resume from an
+// arbitrary bool value once a variant has overridden it (the derive now
+// rejects that combination at compile time), so every variant after an
+// override must keep spelling out its own discriminant.
+#[derive(Debug, PartialEq, XDREnumSerialize, XDREnumDeserialize)]
+#[xdr(discriminant_type = "bool")]
+enum ToggleWithOverride {
+    #[xdr(discriminant = false)]
+    Off,
+    #[xdr(discriminant = true)]
+    On(u32),
+}
+
+#[test]
+fn round_trips_enum_path_discriminant_type() {
+    for original in [Tagged::First(9), Tagged::Second] {
+        let value = common::to_value(&original, false);
+        let restored: Tagged = common::from_value(value, false);
+        assert_eq!(original, restored);
+    }
+}
+
+#[test]
+fn round_trips_bool_discriminant_type() {
+    for original in [Toggle::Off, Toggle::On(5)] {
+        let value = common::to_value(&original, false);
+        let restored: Toggle = common::from_value(value, false);
+        assert_eq!(original, restored);
+    }
+}
+
+#[test]
+fn round_trips_bool_discriminant_type_with_explicit_overrides() {
+    for original in [ToggleWithOverride::Off, ToggleWithOverride::On(5)] {
+        let value = common::to_value(&original, false);
+        let restored: ToggleWithOverride = common::from_value(value, false);
+        assert_eq!(original, restored);
+    }
+}
+
+// Regression test: `KindNonCopy` below only derives `Clone`, not `Copy`.
+// The generated `visit_seq` used to cast the runtime `discriminant` value
+// directly (`discriminant as u32`), which moves it; with two or more
+// non-default branches that tripped E0382 on the second comparison unless
+// the discriminant_type happened to be `Copy`, masking the bug in `Tagged`
+// above.
+#[derive(Debug, Clone, PartialEq)]
+enum KindNonCopy {
+    A,
+    B,
+    C,
+}
+
+impl serde::Serialize for KindNonCopy {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let n: u32 = match self {
+            KindNonCopy::A => 0,
+            KindNonCopy::B => 1,
+            KindNonCopy::C => 2,
+        };
+        serializer.serialize_u32(n)
+    }
+}
+
+impl<'de> serde::Deserialize<'de> for KindNonCopy {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let n = u32::deserialize(deserializer)?;
+        match n {
+            0 => Ok(KindNonCopy::A),
+            1 => Ok(KindNonCopy::B),
+            2 => Ok(KindNonCopy::C),
+            other => Err(serde::de::Error::custom(format!("unknown KindNonCopy {other}"))),
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, XDREnumSerialize, XDREnumDeserialize)]
+#[xdr(discriminant_type = "KindNonCopy")]
+enum TaggedNonCopy {
+    #[xdr(discriminant = KindNonCopy::A)]
+    First(u32),
+    #[xdr(discriminant = KindNonCopy::B)]
+    Second,
+    #[xdr(discriminant = KindNonCopy::C)]
+    Third,
+}
+
+#[test]
+fn round_trips_non_copy_enum_path_discriminant_type() {
+    for original in [
+        TaggedNonCopy::First(9),
+        TaggedNonCopy::Second,
+        TaggedNonCopy::Third,
+    ] {
+        let value = common::to_value(&original, false);
+        let restored: TaggedNonCopy = common::from_value(value, false);
+        assert_eq!(original, restored);
+    }
+}
@@ -0,0 +1,86 @@
+#[path = "common/mod.rs"]
+mod common;
+
+use std::marker::PhantomData;
+
+use xdr_brk_enum::{XDREnumDeserialize, XDREnumSerialize};
+
+#[derive(Debug, PartialEq, XDREnumSerialize, XDREnumDeserialize)]
+enum Wrapper<T> {
+    Value(T),
+    #[xdr(default)]
+    Unknown(u32),
+}
+
+#[test]
+fn round_trips_generic_variant() {
+    let original = Wrapper::Value(7u32);
+    let value = common::to_value(&original, false);
+    let restored: Wrapper<u32> = common::from_value(value, false);
+    assert_eq!(original, restored);
+}
+
+// Regression test for the generated `__Visitor` threading the enum's own
+// lifetime parameters, not just its type parameters: a lifetime that only
+// appears in `impl_generics` and not in `__Visitor`'s own generics trips
+// E0207 ("lifetime parameter is not constrained").
+#[derive(Debug, PartialEq, XDREnumSerialize, XDREnumDeserialize)]
+enum Borrowing<'a> {
+    Marker(PhantomData<&'a ()>),
+    Value(u32),
+}
+
+#[test]
+fn round_trips_lifetime_variant() {
+    let original = Borrowing::Value(7u32);
+    let value = common::to_value(&original, false);
+    let restored: Borrowing<'_> = common::from_value(value, false);
+    assert_eq!(original, restored);
+}
+
+// `T::Output` is a two-segment path, so `bound::collect_idents_in_type`'s
+// `segments.len() == 1` check never recognizes `T` as used: the inferred
+// bound would leave the generated impls without `T::Output: Serialize` /
+// `DeserializeOwned`, even though the field needs it. `#[xdr(bound = "...")]`
+// is the escape hatch for exactly this case.
+trait HasOutput {
+    type Output;
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Marker;
+
+impl HasOutput for Marker {
+    type Output = Wrapped;
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct Wrapped(u32);
+
+impl serde::Serialize for Wrapped {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_u32(self.0)
+    }
+}
+
+impl<'de> serde::Deserialize<'de> for Wrapped {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        Ok(Wrapped(u32::deserialize(deserializer)?))
+    }
+}
+
+#[derive(Debug, PartialEq, XDREnumSerialize, XDREnumDeserialize)]
+#[xdr(bound = "T::Output: ::serde::Serialize, T::Output: ::serde::de::DeserializeOwned")]
+enum Indirect<T: HasOutput> {
+    Value(T::Output),
+    #[xdr(default)]
+    Unknown(u32),
+}
+
+#[test]
+fn round_trips_with_explicit_bound_override() {
+    let original = Indirect::<Marker>::Value(Wrapped(9));
+    let value = common::to_value(&original, false);
+    let restored: Indirect<Marker> = common::from_value(value, false);
+    assert_eq!(original, restored);
+}
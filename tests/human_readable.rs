@@ -0,0 +1,54 @@
+#[path = "common/mod.rs"]
+mod common;
+
+use serde::Deserialize;
+use xdr_brk_enum::{XDREnumDeserialize, XDREnumSerialize};
+
+#[derive(Debug, PartialEq, XDREnumSerialize, XDREnumDeserialize)]
+enum Message {
+    Ping,
+    Text(String),
+    Pair { a: u32, b: u32 },
+    #[xdr(default)]
+    Unknown(u32),
+}
+
+#[test]
+fn round_trips_human_readable_form() {
+    for original in [
+        Message::Ping,
+        Message::Text("hi".to_string()),
+        Message::Pair { a: 1, b: 2 },
+    ] {
+        let value = common::to_value(&original, true);
+        let restored: Message = common::from_value(value, true);
+        assert_eq!(original, restored);
+    }
+}
+
+#[test]
+fn human_readable_form_is_externally_tagged() {
+    let value = common::to_value(&Message::Text("hi".to_string()), true);
+    match value {
+        common::Value::Map(entries) => {
+            assert_eq!(entries.len(), 1);
+            assert_eq!(entries[0].0, common::Value::Str("Text".to_string()));
+        }
+        other => panic!("expected an externally-tagged map, got {other:?}"),
+    }
+}
+
+// Unlike the binary `visit_seq` form, the human-readable `visit_map` form
+// never falls back to `#[xdr(default)]` for an unrecognized key: there's no
+// unmatched numeric discriminant to hand the default variant, only the key
+// string itself. This is intentional, not a gap — see the comment above the
+// `Err` in the derive's `visit_map`.
+#[test]
+fn human_readable_form_errors_on_unrecognized_key_even_with_default_variant() {
+    let value = common::Value::Map(vec![(
+        common::Value::Str("NotAVariant".to_string()),
+        common::Value::U32(7),
+    )]);
+    let result = Message::deserialize(common::ValueDeserializer::new(value, true));
+    assert!(result.is_err());
+}
@@ -13,6 +13,6 @@ enum MyEnum {
         a: u32,
         b: String,
     },
-    #[default_arm]
+    #[xdr(default)]
     Variant4(u8),
 }
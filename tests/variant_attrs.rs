@@ -0,0 +1,30 @@
+#[path = "common/mod.rs"]
+mod common;
+
+use serde::Serialize;
+use xdr_brk_enum::{XDREnumDeserialize, XDREnumSerialize};
+
+#[derive(Debug, PartialEq, XDREnumSerialize, XDREnumDeserialize)]
+enum WithSkip {
+    Alpha,
+    #[xdr(skip)]
+    Hidden(u32),
+    Beta(u32),
+    #[xdr(default)]
+    Unknown(u32),
+}
+
+#[test]
+fn round_trips_non_skipped_variants() {
+    for original in [WithSkip::Alpha, WithSkip::Beta(3)] {
+        let value = common::to_value(&original, false);
+        let restored: WithSkip = common::from_value(value, false);
+        assert_eq!(original, restored);
+    }
+}
+
+#[test]
+fn serializing_a_skipped_variant_errors_instead_of_panicking() {
+    let result = WithSkip::Hidden(42).serialize(common::ValueSerializer { human_readable: false });
+    assert!(result.is_err());
+}